@@ -0,0 +1,104 @@
+// Route guards: predicates checked against a request before a route is
+// allowed to dispatch. Let two routes share the same path and be
+// disambiguated by header, host, or content-type instead of only by method.
+
+use actix_web::dev::RequestHead;
+use actix_web::http::header;
+use std::sync::Arc;
+
+/// A predicate checked against the request head before a route dispatches.
+pub trait Guard: Send + Sync {
+    /// Return `true` if this guard allows the request through.
+    fn check(&self, req: &RequestHead) -> bool;
+}
+
+/// Adapts an [`Guard`] so it can be chained onto an actix `Route` via
+/// `Route::guard`.
+pub(crate) struct GuardAdapter(pub Arc<dyn Guard>);
+
+impl actix_web::guard::Guard for GuardAdapter {
+    fn check(&self, ctx: &actix_web::guard::GuardContext<'_>) -> bool {
+        self.0.check(ctx.head())
+    }
+}
+
+/// Matches requests carrying a header with the given name and value.
+pub struct Header {
+    name: String,
+    value: String,
+}
+
+impl Header {
+    pub fn new(name: &str, value: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            value: value.to_string(),
+        }
+    }
+}
+
+impl Guard for Header {
+    fn check(&self, req: &RequestHead) -> bool {
+        req.headers()
+            .get(self.name.as_str())
+            .and_then(|v| v.to_str().ok())
+            .map(|v| v == self.value)
+            .unwrap_or(false)
+    }
+}
+
+/// Matches requests addressed to the given `Host` header.
+pub struct Host {
+    host: String,
+}
+
+impl Host {
+    pub fn new(host: &str) -> Self {
+        Self {
+            host: host.to_string(),
+        }
+    }
+}
+
+impl Guard for Host {
+    fn check(&self, req: &RequestHead) -> bool {
+        req.headers()
+            .get(header::HOST)
+            .and_then(|v| v.to_str().ok())
+            .map(|v| v == self.host)
+            .unwrap_or(false)
+    }
+}
+
+/// Matches requests whose `Content-Type` base media type (i.e. ignoring any
+/// `; charset=...`-style parameters) equals the given value, ignoring ASCII
+/// case, e.g.
+/// `ContentType::new("application/json")` matches `application/json` and
+/// `application/json; charset=utf-8` but not `application/json-patch+json`.
+pub struct ContentType {
+    content_type: String,
+}
+
+impl ContentType {
+    pub fn new(content_type: &str) -> Self {
+        Self {
+            content_type: content_type.to_string(),
+        }
+    }
+}
+
+impl Guard for ContentType {
+    fn check(&self, req: &RequestHead) -> bool {
+        req.headers()
+            .get(header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .map(|v| {
+                v.split(';')
+                    .next()
+                    .unwrap_or("")
+                    .trim()
+                    .eq_ignore_ascii_case(&self.content_type)
+            })
+            .unwrap_or(false)
+    }
+}
@@ -0,0 +1,8 @@
+pub mod guard;
+pub mod middleware;
+pub mod router;
+pub mod server;
+pub mod test_server;
+pub mod web;
+
+pub use server::{HttpMethod, IronnServer, PubRoute};
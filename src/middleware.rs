@@ -0,0 +1,149 @@
+// Cross-cutting request/response logic: a `Middleware` runs around the rest
+// of the chain. `Next` (rather than actix's own `middleware::Next<B>`) lets
+// any number of `IronnServer::wrap`-ped middlewares fold into the single
+// `App::wrap` call actix's `App` type allows per layer.
+
+use actix_web::body::BoxBody;
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::http::header;
+use actix_web::{Error, HttpResponse};
+use std::future::Future;
+use std::pin::Pin;
+use std::time::Instant;
+
+type BoxResponseFuture = Pin<Box<dyn Future<Output = Result<ServiceResponse<BoxBody>, Error>>>>;
+
+/// The rest of the middleware chain (and, eventually, the route handler).
+pub struct Next(Box<dyn FnOnce(ServiceRequest) -> BoxResponseFuture>);
+
+impl Next {
+    pub(crate) fn new(f: impl FnOnce(ServiceRequest) -> BoxResponseFuture + 'static) -> Self {
+        Self(Box::new(f))
+    }
+
+    /// Continue the chain with `req`.
+    pub fn call(self, req: ServiceRequest) -> BoxResponseFuture {
+        (self.0)(req)
+    }
+}
+
+/// Runs around every request. Call `next.call(req)` to continue the chain;
+/// returning without calling it short-circuits the request with whatever
+/// response is produced instead.
+pub trait Middleware: Send + Sync {
+    fn handle(&self, req: ServiceRequest, next: Next) -> BoxResponseFuture;
+}
+
+/// Logs method, path, status, and elapsed time for every request.
+pub struct Logger;
+
+impl Middleware for Logger {
+    fn handle(&self, req: ServiceRequest, next: Next) -> BoxResponseFuture {
+        Box::pin(async move {
+            let method = req.method().clone();
+            let path = req.path().to_string();
+            let started = Instant::now();
+            let res = next.call(req).await?;
+            println!(
+                "{} {} -> {} ({:?})",
+                method,
+                path,
+                res.status().as_u16(),
+                started.elapsed()
+            );
+            Ok(res)
+        })
+    }
+}
+
+/// Rejects requests whose `Authorization` header isn't `Bearer <token>`
+/// with a 401, short-circuiting before the route handler runs.
+pub struct BearerAuth {
+    token: String,
+}
+
+impl BearerAuth {
+    pub fn new(token: impl Into<String>) -> Self {
+        Self {
+            token: token.into(),
+        }
+    }
+}
+
+impl Middleware for BearerAuth {
+    fn handle(&self, req: ServiceRequest, next: Next) -> BoxResponseFuture {
+        let token = self.token.clone();
+        Box::pin(async move {
+            let authorized = req
+                .headers()
+                .get(header::AUTHORIZATION)
+                .and_then(|v| v.to_str().ok())
+                .map(|v| v == format!("Bearer {}", token))
+                .unwrap_or(false);
+
+            if authorized {
+                next.call(req).await
+            } else {
+                Ok(req.into_response(HttpResponse::Unauthorized().finish()))
+            }
+        })
+    }
+}
+
+/// Run `middlewares[idx..]` in order, then hand off to `tail` (the actual
+/// actix service chain) once the list is exhausted.
+pub(crate) fn run_chain(
+    middlewares: std::sync::Arc<Vec<std::sync::Arc<dyn Middleware>>>,
+    idx: usize,
+    req: ServiceRequest,
+    tail: impl FnOnce(ServiceRequest) -> BoxResponseFuture + 'static,
+) -> BoxResponseFuture {
+    match middlewares.get(idx) {
+        Some(mw) => {
+            let mw = std::sync::Arc::clone(mw);
+            let rest = std::sync::Arc::clone(&middlewares);
+            mw.handle(req, Next::new(move |req| run_chain(rest, idx + 1, req, tail)))
+        }
+        None => tail(req),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::test::TestRequest;
+    use std::sync::{Arc, Mutex};
+
+    struct Record(&'static str, Arc<Mutex<Vec<&'static str>>>);
+
+    impl Middleware for Record {
+        fn handle(&self, req: ServiceRequest, next: Next) -> BoxResponseFuture {
+            let label = self.0;
+            let log = Arc::clone(&self.1);
+            Box::pin(async move {
+                log.lock().unwrap().push(label);
+                let res = next.call(req).await;
+                log.lock().unwrap().push(label);
+                res
+            })
+        }
+    }
+
+    #[actix_web::test]
+    async fn middlewares_run_outermost_first_innermost_last() {
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let middlewares: Arc<Vec<Arc<dyn Middleware>>> = Arc::new(vec![
+            Arc::new(Record("outer", Arc::clone(&log))),
+            Arc::new(Record("inner", Arc::clone(&log))),
+        ]);
+        let req = TestRequest::default().to_srv_request();
+
+        run_chain(middlewares, 0, req, |req| {
+            Box::pin(async move { Ok(req.into_response(HttpResponse::Ok().finish())) })
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(*log.lock().unwrap(), vec!["outer", "inner", "inner", "outer"]);
+    }
+}
@@ -1,4 +1,6 @@
+use crate::server::HttpMethod;
 use actix_web::HttpResponse;
+use std::collections::HashMap;
 
 #[derive(Clone)]
 pub struct Route {
@@ -6,7 +8,7 @@ pub struct Route {
     pub method: Method,
 }
 
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub enum Method {
     GET,
     POST,
@@ -14,6 +16,97 @@ pub enum Method {
     DELETE,
 }
 
-pub trait RouterHandler {
-    fn handle(&self) -> HttpResponse;
-}
\ No newline at end of file
+impl From<Method> for HttpMethod {
+    fn from(method: Method) -> Self {
+        match method {
+            Method::GET => HttpMethod::Get,
+            Method::POST => HttpMethod::Post,
+            Method::PUT => HttpMethod::Put,
+            Method::DELETE => HttpMethod::Delete,
+        }
+    }
+}
+
+impl From<HttpMethod> for Method {
+    fn from(method: HttpMethod) -> Self {
+        match method {
+            HttpMethod::Get => Method::GET,
+            HttpMethod::Post => Method::POST,
+            HttpMethod::Put => Method::PUT,
+            HttpMethod::Delete => Method::DELETE,
+        }
+    }
+}
+
+/// Dynamic path segments captured while matching a `{name}` pattern, e.g.
+/// `/users/{id}` against `/users/42`.
+#[derive(Clone, Debug, Default)]
+pub struct PathParams(HashMap<String, String>);
+
+impl PathParams {
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.0.get(name).map(String::as_str)
+    }
+}
+
+/// An alternative registration path to `IronnServer::public_route`: users
+/// implement this directly on their own types and register them with
+/// `IronnServer::mount`, rather than handing over a closure.
+pub trait RouterHandler: Send + Sync {
+    fn handle(&self, params: &PathParams) -> HttpResponse;
+}
+
+/// Match `path` against an actix `ResourceDef`-style pattern such as
+/// `/users/{id}`, returning the captured segments on success.
+pub(crate) fn match_pattern(pattern: &str, path: &str) -> Option<PathParams> {
+    let pattern_segments = pattern.trim_matches('/').split('/');
+    let mut path_segments = path.trim_matches('/').split('/');
+
+    let mut params = HashMap::new();
+    for pat in pattern_segments {
+        let value = path_segments.next()?;
+        match pat.strip_prefix('{').and_then(|s| s.strip_suffix('}')) {
+            Some(name) => {
+                params.insert(name.to_string(), value.to_string());
+            }
+            None if pat == value => {}
+            None => return None,
+        }
+    }
+    if path_segments.next().is_some() {
+        return None;
+    }
+    Some(PathParams(params))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_dynamic_segment() {
+        let params = match_pattern("/users/{id}", "/users/42").unwrap();
+        assert_eq!(params.get("id"), Some("42"));
+    }
+
+    #[test]
+    fn matches_root_path() {
+        assert!(match_pattern("/", "/").is_some());
+    }
+
+    #[test]
+    fn ignores_leading_and_trailing_slashes() {
+        assert!(match_pattern("/users/{id}/", "users/42/").is_some());
+    }
+
+    #[test]
+    fn rejects_mismatched_segment_count() {
+        assert!(match_pattern("/users/{id}", "/users/42/extra").is_none());
+        assert!(match_pattern("/users/{id}", "/users").is_none());
+    }
+
+    #[test]
+    fn rejects_literal_segment_mismatch() {
+        assert!(match_pattern("/users/{id}", "/orders/42").is_none());
+    }
+}
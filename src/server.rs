@@ -1,8 +1,18 @@
-use actix_web::{test, web, App, HttpResponse, HttpServer, Result as ActixResult};
+use actix_web::{
+    dev::Payload, test, web, App, HttpRequest, HttpResponse, HttpServer, Result as ActixResult,
+};
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
 use std::future::Future;
 use std::pin::Pin;
 use std::sync::Arc;
 
+use crate::guard::{Guard, GuardAdapter};
+use crate::middleware::{run_chain, Middleware};
+use crate::router::route::{match_pattern, Route, RouterHandler};
+use crate::web::{AppDataMap, FromRequest};
+use actix_web::middleware::from_fn;
+
 #[derive(Clone)]
 pub enum HttpMethod {
     Get,
@@ -11,9 +21,36 @@ pub enum HttpMethod {
     Delete,
 }
 
-// Simple handler type - just async functions that return HttpResponse
-type Handler =
-    Arc<dyn Fn() -> Pin<Box<dyn Future<Output = ActixResult<HttpResponse>> + Send>> + Send + Sync>;
+impl HttpMethod {
+    fn as_actix(&self) -> actix_web::http::Method {
+        match self {
+            HttpMethod::Get => actix_web::http::Method::GET,
+            HttpMethod::Post => actix_web::http::Method::POST,
+            HttpMethod::Put => actix_web::http::Method::PUT,
+            HttpMethod::Delete => actix_web::http::Method::DELETE,
+        }
+    }
+}
+
+// A route registered via `IronnServer::mount`: dispatches to a
+// `RouterHandler` implementation instead of a closure, and is matched by
+// our own dynamic-segment router rather than actix's path matching.
+#[derive(Clone)]
+struct MountedRoute {
+    route: Route,
+    handler: Arc<dyn RouterHandler>,
+}
+
+// Handler type - erased so routes with differently-typed extractor
+// arguments can live side by side in the same `Vec<PubRoute>`. Extraction
+// (if any) happens inside the closure itself, driven by `FromRequest`. The
+// returned future isn't `Send` - actix runs each worker single-threaded, and
+// `FromRequest::Future` (which `route_with` awaits here) isn't `Send` either.
+type Handler = Arc<
+    dyn Fn(HttpRequest, Payload) -> Pin<Box<dyn Future<Output = ActixResult<HttpResponse>>>>
+        + Send
+        + Sync,
+>;
 
 // Simple route definition
 #[derive(Clone)]
@@ -21,30 +58,249 @@ pub struct PubRoute {
     pub path: String,
     pub method: HttpMethod,
     pub handler: Handler,
+    pub guards: Vec<Arc<dyn Guard>>,
+}
+
+// Fallback reached when no static `PubRoute`/`route_with` path matched:
+// try the dynamic-segment `mount`-ed routes first, then the user's own
+// `default_handler`, then a plain 404 - mirroring the "if handler is not
+// explicitly set, default 404 Not Found handler is used" behavior of
+// actix's own route matching.
+fn mounted_fallback(mounted: Arc<Vec<MountedRoute>>, default_handler: Option<Handler>) -> Handler {
+    Arc::new(move |req, payload| {
+        let mounted = Arc::clone(&mounted);
+        let default_handler = default_handler.clone();
+        Box::pin(async move {
+            for mounted_route in mounted.iter() {
+                let method: HttpMethod = mounted_route.route.method.clone().into();
+                if method.as_actix() == *req.method() {
+                    if let Some(params) = match_pattern(&mounted_route.route.path, req.path()) {
+                        return Ok(mounted_route.handler.handle(&params));
+                    }
+                }
+            }
+            match default_handler {
+                Some(handler) => handler(req, payload).await,
+                None => Ok(HttpResponse::NotFound().finish()),
+            }
+        })
+    })
+}
+
+// Build the actix `Route` for a `PubRoute`: pick the method builder, chain
+// on any guards, then wire in the erased handler.
+fn build_route(route: &PubRoute) -> actix_web::Route {
+    let handler = Arc::clone(&route.handler);
+    let mut actix_route = match route.method {
+        HttpMethod::Get => web::get(),
+        HttpMethod::Post => web::post(),
+        HttpMethod::Put => web::put(),
+        HttpMethod::Delete => web::delete(),
+    };
+    for g in &route.guards {
+        actix_route = actix_route.guard(GuardAdapter(Arc::clone(g)));
+    }
+    actix_route.to(move |req: HttpRequest, payload: Payload| {
+        let handler = Arc::clone(&handler);
+        async move { handler(req, payload).await }
+    })
+}
+
+// Group routes by path, preserving the order each distinct path was first
+// registered in (and the registration order of routes within a path).
+// `App::route` registers one `web::resource(path)` per call, so two routes
+// sharing a path would otherwise become two separate resources - and actix
+// only ever dispatches to the first resource matching a path, never falling
+// through to the second. Grouping here so all routes for a path land on one
+// `Resource` is what actually lets guards (rather than just method) tell
+// same-path routes apart.
+fn group_routes(routes: &[PubRoute]) -> Vec<(&str, Vec<&PubRoute>)> {
+    let mut order: Vec<&str> = Vec::new();
+    let mut groups: HashMap<&str, Vec<&PubRoute>> = HashMap::new();
+
+    for route in routes {
+        groups
+            .entry(route.path.as_str())
+            .or_insert_with(|| {
+                order.push(route.path.as_str());
+                Vec::new()
+            })
+            .push(route);
+    }
+
+    order
+        .into_iter()
+        .map(|path| (path, groups.remove(path).unwrap()))
+        .collect()
+}
+
+// Build the single `Resource` serving all `PubRoute`s registered for `path`:
+// one `.route(...)` per route (actix tries them in order and falls through
+// to the next on a guard/method mismatch), plus a `default_service` that
+// calls `default_handler` (or 404) when none of them match - so a guard
+// mismatch reaches the user's fallback instead of actix's own 404/405.
+fn build_resource(
+    path: &str,
+    routes: &[&PubRoute],
+    default_handler: Option<Handler>,
+) -> impl actix_web::dev::HttpServiceFactory {
+    let mut resource = web::resource(path);
+    for route in routes {
+        resource = resource.route(build_route(route));
+    }
+    resource.default_service(web::route().to(
+        move |req: HttpRequest, payload: Payload| {
+            let default_handler = default_handler.clone();
+            async move {
+                match default_handler {
+                    Some(handler) => handler(req, payload).await,
+                    None => Ok(HttpResponse::NotFound().finish()),
+                }
+            }
+        },
+    ))
 }
 
 //MAIN API:
+#[derive(Clone)]
 pub struct IronnServer {
     routes: Vec<PubRoute>,
+    mounted: Vec<MountedRoute>,
+    default_handler: Option<Handler>,
+    app_data: AppDataMap,
+    middlewares: Vec<Arc<dyn Middleware>>,
 }
 
 impl IronnServer {
     /// Create a new IronnServer instance
     pub fn new() -> Self {
-        Self { routes: Vec::new() }
+        Self {
+            routes: Vec::new(),
+            mounted: Vec::new(),
+            default_handler: None,
+            app_data: AppDataMap::new(),
+            middlewares: Vec::new(),
+        }
+    }
+
+    /// Register a [`RouterHandler`] implementation at `path`, matched by
+    /// our own dynamic-segment router (e.g. `/users/{id}`) rather than by
+    /// handing over a closure like `public_route`/`route_with` do.
+    pub fn mount<H>(mut self, path: &str, method: impl Into<HttpMethod>, handler: H) -> Self
+    where
+        H: RouterHandler + 'static,
+    {
+        let method: HttpMethod = method.into();
+        self.mounted.push(MountedRoute {
+            route: Route {
+                path: path.to_string(),
+                method: method.into(),
+            },
+            handler: Arc::new(handler),
+        });
+        self
+    }
+
+    /// Register application-wide shared state, retrievable inside handlers
+    /// via a `web::Data<T>` argument. One entry per concrete type `T`;
+    /// registering the same type twice replaces the previous value.
+    pub fn app_data<T>(mut self, data: Arc<T>) -> Self
+    where
+        T: Send + Sync + 'static,
+    {
+        self.app_data
+            .insert(TypeId::of::<T>(), data as Arc<dyn Any + Send + Sync>);
+        self
+    }
+
+    /// Register a middleware to run around every request. Middlewares run
+    /// in registration order (the first one `wrap`-ped is outermost, so it
+    /// sees the request first and the response last).
+    pub fn wrap<M>(mut self, mw: M) -> Self
+    where
+        M: Middleware + 'static,
+    {
+        self.middlewares.push(Arc::new(mw));
+        self
     }
 
     /// Add a public route to the server
     pub fn public_route<F, Fut>(mut self, path: &str, method: HttpMethod, handler: F) -> Self
     where
         F: Fn() -> Fut + Send + Sync + 'static,
-        Fut: Future<Output = ActixResult<HttpResponse>> + Send + 'static,
+        Fut: Future<Output = ActixResult<HttpResponse>> + 'static,
+    {
+        let handler: Handler = Arc::new(move |_req, _payload| Box::pin(handler()));
+        self.routes.push(PubRoute {
+            path: path.to_string(),
+            handler,
+            method,
+            guards: Vec::new(),
+        });
+        self
+    }
+
+    /// Attach a guard to the most recently added route, so it only
+    /// dispatches when the guard (in addition to the method match) passes.
+    /// Multiple `.guard(...)` calls are all required to pass (logical AND),
+    /// which lets two routes share a path and be told apart by header,
+    /// host, or content-type.
+    ///
+    /// ```ignore
+    /// server
+    ///     .public_route("/widgets", HttpMethod::Post, create_widget)
+    ///     .guard(guard::ContentType::new("application/json"));
+    /// ```
+    pub fn guard<G>(mut self, g: G) -> Self
+    where
+        G: Guard + 'static,
+    {
+        let route = self
+            .routes
+            .last_mut()
+            .expect("guard() called with no route registered - call public_route/route_with first");
+        route.guards.push(Arc::new(g));
+        self
+    }
+
+    /// Register a fallback handler invoked when no route (or no route's
+    /// guards) match the incoming request, instead of actix's default 404.
+    pub fn default_handler<F, Fut>(mut self, handler: F) -> Self
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ActixResult<HttpResponse>> + 'static,
+    {
+        self.default_handler = Some(Arc::new(move |_req, _payload| Box::pin(handler())));
+        self
+    }
+
+    /// Add a public route whose handler takes extracted request data, e.g.
+    /// `web::Path<u32>`, `web::Query<Filters>`, or `web::Json<Body>`.
+    ///
+    /// ```ignore
+    /// server.route_with("/users/{id}", HttpMethod::Get, |id: web::Path<u32>| async move {
+    ///     Ok(HttpResponse::Ok().json(*id))
+    /// });
+    /// ```
+    pub fn route_with<F, Args, Fut>(mut self, path: &str, method: HttpMethod, handler: F) -> Self
+    where
+        F: Fn(Args) -> Fut + Send + Sync + 'static,
+        Args: FromRequest + 'static,
+        Fut: Future<Output = ActixResult<HttpResponse>> + 'static,
     {
-        let handler: Handler = Arc::new(move || Box::pin(handler()));
+        let handler = Arc::new(handler);
+        let handler: Handler = Arc::new(move |req, mut payload| {
+            let handler = Arc::clone(&handler);
+            Box::pin(async move {
+                let args = Args::from_request(&req, &mut payload).await?;
+                handler(args).await
+            })
+        });
         self.routes.push(PubRoute {
             path: path.to_string(),
             handler,
             method,
+            guards: Vec::new(),
         });
         self
     }
@@ -57,57 +313,41 @@ impl IronnServer {
     /// Start the HTTP server on a custom address
     pub async fn bind(self, address: &str) -> Result<(), std::io::Error> {
         let routes = Arc::new(self.routes);
+        let mounted = Arc::new(self.mounted);
+        let default_handler = self.default_handler.clone();
+        let app_data = Arc::new(self.app_data);
+        let middlewares = Arc::new(self.middlewares);
 
         println!("🚀 IronnServer starting on http://{}", address);
         println!("📋 Routes registered: {}", routes.len());
 
         HttpServer::new(move || {
             let routes = Arc::clone(&routes);
-            let mut app = App::new();
-
-            for route in routes.iter() {
-                let handler = Arc::clone(&route.handler);
-                println!("📍 Registering: GET {}", route.path);
-
-                match &route.method {
-                    HttpMethod::Get => {
-                        app = app.route(
-                            &route.path,
-                            web::get().to(move || {
-                                let handler = Arc::clone(&handler);
-                                async move { handler().await }
-                            }),
-                        );
-                    }
-                    HttpMethod::Post => {
-                        app = app.route(
-                            &route.path,
-                            web::post().to(move || {
-                                let handler = Arc::clone(&handler);
-                                async move { handler().await }
-                            }),
-                        );
-                    }
-                    HttpMethod::Put => {
-                        app = app.route(
-                            &route.path,
-                            web::put().to(move || {
-                                let handler = Arc::clone(&handler);
-                                async move { handler().await }
-                            }),
-                        );
-                    }
-                    HttpMethod::Delete => {
-                        app = app.route(
-                            &route.path,
-                            web::delete().to(move || {
-                                let handler = Arc::clone(&handler);
-                                async move { handler().await }
-                            }),
-                        );
-                    }
-                }
+            let mut app = App::new().app_data(Arc::clone(&app_data));
+
+            for (path, group) in group_routes(&routes) {
+                println!("📍 Registering: {} ({} route(s))", path, group.len());
+                app = app.service(build_resource(path, &group, default_handler.clone()));
             }
+
+            if !middlewares.is_empty() {
+                let middlewares = Arc::clone(&middlewares);
+                app = app.wrap(from_fn(move |req, actix_next| {
+                    let middlewares = Arc::clone(&middlewares);
+                    run_chain(middlewares, 0, req, move |req| {
+                        Box::pin(actix_next.call(req))
+                    })
+                }));
+            }
+
+            let fallback = mounted_fallback(Arc::clone(&mounted), default_handler.clone());
+            app = app.default_service(web::route().to(
+                move |req: HttpRequest, payload: Payload| {
+                    let fallback = Arc::clone(&fallback);
+                    async move { fallback(req, payload).await }
+                },
+            ));
+
             app
         })
         .bind(address)?
@@ -120,52 +360,41 @@ impl IronnServer {
         self.routes.len()
     }
 
+    /// Spin up a real HTTP server bound to an ephemeral `127.0.0.1` port on
+    /// a background runtime, for integration tests that need to exercise
+    /// actual TCP/bind behavior rather than `call_service` against an
+    /// in-memory `App`. The server is torn down when the returned
+    /// [`crate::test_server::TestServer`] is dropped.
+    pub fn test_server(self) -> crate::test_server::TestServer {
+        crate::test_server::TestServer::start(move || self.clone().create_app())
+    }
+
     /// Create an App for testing (internal use)
     pub fn create_app(
         self,
     ) -> App<impl actix_web::dev::ServiceFactory<actix_web::dev::ServiceRequest>> {
-        let mut app = App::new();
-        for route in self.routes.iter() {
-            let handler = Arc::clone(&route.handler);
-            match &route.method {
-                HttpMethod::Get => {
-                    app = app.route(
-                        &route.path,
-                        web::get().to(move || {
-                            let handler = Arc::clone(&handler);
-                            async move { handler().await }
-                        }),
-                    );
-                }
-                HttpMethod::Post => {
-                    app = app.route(
-                        &route.path,
-                        web::post().to(move || {
-                            let handler = Arc::clone(&handler);
-                            async move { handler().await }
-                        }),
-                    );
-                }
-                HttpMethod::Put => {
-                    app = app.route(
-                        &route.path,
-                        web::put().to(move || {
-                            let handler = Arc::clone(&handler);
-                            async move { handler().await }
-                        }),
-                    );
-                }
-                HttpMethod::Delete => {
-                    app = app.route(
-                        &route.path,
-                        web::delete().to(move || {
-                            let handler = Arc::clone(&handler);
-                            async move { handler().await }
-                        }),
-                    );
-                }
-            }
+        let mut app = App::new().app_data(Arc::new(self.app_data));
+        let default_handler = self.default_handler.clone();
+        for (path, group) in group_routes(&self.routes) {
+            app = app.service(build_resource(path, &group, default_handler.clone()));
+        }
+
+        if !self.middlewares.is_empty() {
+            let middlewares = Arc::new(self.middlewares);
+            app = app.wrap(from_fn(move |req, actix_next| {
+                let middlewares = Arc::clone(&middlewares);
+                run_chain(middlewares, 0, req, move |req| Box::pin(actix_next.call(req)))
+            }));
         }
+
+        let fallback = mounted_fallback(Arc::new(self.mounted), self.default_handler);
+        app = app.default_service(web::route().to(
+            move |req: HttpRequest, payload: Payload| {
+                let fallback = Arc::clone(&fallback);
+                async move { fallback(req, payload).await }
+            },
+        ));
+
         app
     }
 }
@@ -313,3 +542,57 @@ pub fn test2() {
 pub fn test3() {
     println!("test3")
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::guard::ContentType;
+    use actix_web::test::{call_service, init_service, TestRequest};
+
+    #[actix_web::test]
+    async fn same_path_routes_are_told_apart_by_guard() {
+        let server = IronnServer::new()
+            .public_route("/widgets", HttpMethod::Post, text_response("json"))
+            .guard(ContentType::new("application/json"))
+            .public_route("/widgets", HttpMethod::Post, text_response("form"))
+            .guard(ContentType::new("application/x-www-form-urlencoded"));
+        let app = init_service(server.create_app()).await;
+
+        let json_req = TestRequest::post()
+            .uri("/widgets")
+            .insert_header(("content-type", "application/json"))
+            .to_request();
+        let json_res = call_service(&app, json_req).await;
+        assert_eq!(get_body_string_from(json_res).await, "json");
+
+        let form_req = TestRequest::post()
+            .uri("/widgets")
+            .insert_header(("content-type", "application/x-www-form-urlencoded"))
+            .to_request();
+        let form_res = call_service(&app, form_req).await;
+        assert_eq!(get_body_string_from(form_res).await, "form");
+    }
+
+    #[actix_web::test]
+    async fn guard_mismatch_reaches_default_handler_not_404() {
+        let server = IronnServer::new()
+            .public_route("/widgets", HttpMethod::Post, text_response("json"))
+            .guard(ContentType::new("application/json"))
+            .default_handler(text_response("fallback"));
+        let app = init_service(server.create_app()).await;
+
+        let req = TestRequest::post()
+            .uri("/widgets")
+            .insert_header(("content-type", "text/plain"))
+            .to_request();
+        let res = call_service(&app, req).await;
+
+        assert_eq!(res.status().as_u16(), 200);
+        assert_eq!(get_body_string_from(res).await, "fallback");
+    }
+
+    async fn get_body_string_from(res: actix_web::dev::ServiceResponse) -> String {
+        let body = actix_web::test::read_body(res).await;
+        String::from_utf8(body.to_vec()).unwrap()
+    }
+}
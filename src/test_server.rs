@@ -0,0 +1,112 @@
+// A real HTTP test server: unlike `call_service` against an in-memory `App`,
+// this binds to an ephemeral port and drives requests over an actual TCP
+// connection, so integration tests exercise the same path production
+// traffic does. The real-HTTP pieces (`TestServer`, `start`) live in the
+// separate `actix-test` crate, not `actix_web::test` - only the in-memory
+// `call_service` helpers stayed in `actix-web` itself.
+
+use actix_service::IntoServiceFactory;
+use actix_web::body::MessageBody;
+use actix_web::dev::{AppConfig, ServiceFactory, ServiceRequest, ServiceResponse};
+use serde::Serialize;
+
+/// A running server plus convenience methods for driving it over real HTTP.
+/// Bound to `127.0.0.1:0` (an OS-assigned ephemeral port) and stopped when
+/// dropped.
+pub struct TestServer {
+    inner: actix_test::TestServer,
+}
+
+/// The outcome of a [`TestServer`] request: status code plus the raw body,
+/// with helpers to decode it the way the `#[cfg(test)]` helpers in
+/// `server.rs` do for in-memory requests.
+pub struct TestResponse {
+    pub status: u16,
+    pub body: Vec<u8>,
+}
+
+impl TestResponse {
+    pub fn text(&self) -> String {
+        String::from_utf8_lossy(&self.body).into_owned()
+    }
+
+    pub fn json<T: serde::de::DeserializeOwned>(&self) -> T {
+        serde_json::from_slice(&self.body).expect("response body was not valid JSON")
+    }
+}
+
+impl TestServer {
+    pub(crate) fn start<F, I, S, B>(factory: F) -> Self
+    where
+        F: Fn() -> I + Send + Clone + 'static,
+        I: IntoServiceFactory<S, ServiceRequest>,
+        S: ServiceFactory<ServiceRequest, Config = AppConfig, Response = ServiceResponse<B>>
+            + 'static,
+        S::Error: std::fmt::Debug + Into<actix_web::Error>,
+        S::InitError: std::fmt::Debug,
+        B: MessageBody + 'static,
+    {
+        Self {
+            inner: actix_test::start(factory),
+        }
+    }
+
+    /// The full URL of `path` on the running server: scheme, host and the
+    /// bound port, followed by `path`.
+    pub fn url(&self, path: &str) -> String {
+        self.inner.url(path)
+    }
+
+    /// Make a real GET request to `path`.
+    pub async fn get(&self, path: &str) -> TestResponse {
+        let mut res = self
+            .inner
+            .get(path)
+            .send()
+            .await
+            .expect("GET request failed");
+        let status = res.status().as_u16();
+        let body = res.body().await.unwrap_or_default().to_vec();
+        TestResponse { status, body }
+    }
+
+    /// Make a real POST request to `path` with a JSON-encoded body.
+    pub async fn post(&self, path: &str, body: impl Serialize) -> TestResponse {
+        let mut res = self
+            .inner
+            .post(path)
+            .send_json(&body)
+            .await
+            .expect("POST request failed");
+        let status = res.status().as_u16();
+        let body = res.body().await.unwrap_or_default().to_vec();
+        TestResponse { status, body }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::server::{text_response, HttpMethod, IronnServer};
+
+    #[actix_web::test]
+    async fn serves_a_registered_route_over_real_http() {
+        let server =
+            IronnServer::new().public_route("/ping", HttpMethod::Get, text_response("pong"));
+        let test_server = server.test_server();
+
+        let res = test_server.get("/ping").await;
+
+        assert_eq!(res.status, 200);
+        assert_eq!(res.text(), "pong");
+    }
+
+    #[actix_web::test]
+    async fn falls_back_to_404_for_an_unregistered_path() {
+        let server = IronnServer::new();
+        let test_server = server.test_server();
+
+        let res = test_server.get("/missing").await;
+
+        assert_eq!(res.status, 404);
+    }
+}
@@ -0,0 +1,248 @@
+// Request-data extractors: a handler closure declares the pieces of the
+// request it needs as typed parameters instead of reaching into a raw
+// `HttpRequest` itself.
+
+use actix_web::{
+    dev::Payload, web as actix_extractors, FromRequest as ActixFromRequest, HttpRequest,
+    Result as ActixResult,
+};
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::future::{Future, Ready};
+use std::ops::Deref;
+use std::pin::Pin;
+use std::sync::Arc;
+
+/// Extracts a typed value out of an incoming request.
+///
+/// Implement this for any type you want to pull directly out of a
+/// [`crate::server::IronnServer::route_with`] handler's argument list.
+// No `Send` bound on `Future`: actix's own extractors aren't Send either
+// (each worker runs single-threaded), so requiring it here would make it
+// impossible to implement this trait at all.
+pub trait FromRequest: Sized {
+    /// Future resolving to the extracted value.
+    type Future: Future<Output = ActixResult<Self>>;
+
+    /// Extract `Self` from the request head and (optionally) its body.
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future;
+}
+
+/// Extracted dynamic path segments, deserialized into `T`.
+#[derive(Clone, Debug)]
+pub struct Path<T>(pub T);
+
+impl<T> Path<T> {
+    /// Unwrap into the inner value.
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> Deref for Path<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> FromRequest for Path<T>
+where
+    T: serde::de::DeserializeOwned + 'static,
+{
+    type Future = Pin<Box<dyn Future<Output = ActixResult<Self>>>>;
+
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        let fut = actix_extractors::Path::<T>::from_request(req, payload);
+        Box::pin(async move { Ok(Path(fut.await?.into_inner())) })
+    }
+}
+
+/// Extracted query-string parameters, deserialized into `T`.
+#[derive(Clone, Debug)]
+pub struct Query<T>(pub T);
+
+impl<T> Query<T> {
+    /// Unwrap into the inner value.
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> Deref for Query<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> FromRequest for Query<T>
+where
+    T: serde::de::DeserializeOwned + 'static,
+{
+    type Future = Pin<Box<dyn Future<Output = ActixResult<Self>>>>;
+
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        let fut = actix_extractors::Query::<T>::from_request(req, payload);
+        Box::pin(async move { Ok(Query(fut.await?.into_inner())) })
+    }
+}
+
+/// Extracted and deserialized JSON request body.
+#[derive(Clone, Debug)]
+pub struct Json<T>(pub T);
+
+impl<T> Json<T> {
+    /// Unwrap into the inner value.
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> Deref for Json<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> FromRequest for Json<T>
+where
+    T: serde::de::DeserializeOwned + 'static,
+{
+    type Future = Pin<Box<dyn Future<Output = ActixResult<Self>>>>;
+
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        let fut = actix_extractors::Json::<T>::from_request(req, payload);
+        Box::pin(async move { Ok(Json(fut.await?.into_inner())) })
+    }
+}
+
+/// Type-erased bag of application-wide state, keyed by the concrete type it
+/// was registered under via `IronnServer::app_data`. Registered once with
+/// actix's own `App::app_data` so every worker/route can reach it.
+pub type AppDataMap = HashMap<TypeId, Arc<dyn Any + Send + Sync>>;
+
+/// Application-wide shared state, injected via `IronnServer::app_data` and
+/// retrieved in a handler by declaring an argument of this type.
+///
+/// ```ignore
+/// server.app_data(Arc::new(MyDb::connect()));
+/// // ...
+/// |db: web::Data<MyDb>| async move { db.query(...).await };
+/// ```
+#[derive(Clone)]
+pub struct Data<T: ?Sized>(Arc<T>);
+
+impl<T: ?Sized> Data<T> {
+    /// Unwrap into the inner `Arc`.
+    pub fn into_inner(self) -> Arc<T> {
+        self.0
+    }
+}
+
+impl<T: ?Sized> Deref for Data<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> FromRequest for Data<T>
+where
+    T: Send + Sync + 'static,
+{
+    type Future = Ready<ActixResult<Self>>;
+
+    fn from_request(req: &HttpRequest, _payload: &mut Payload) -> Self::Future {
+        let data = req
+            .app_data::<Arc<AppDataMap>>()
+            .and_then(|map| map.get(&TypeId::of::<T>()))
+            .cloned()
+            .and_then(|value| value.downcast::<T>().ok());
+
+        std::future::ready(data.map(Data).ok_or_else(|| {
+            actix_web::error::ErrorInternalServerError(format!(
+                "app data not registered for type `{}`",
+                std::any::type_name::<T>()
+            ))
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::test::TestRequest;
+    use serde::Deserialize;
+
+    #[derive(Deserialize)]
+    struct Id {
+        id: u32,
+    }
+
+    #[actix_web::test]
+    async fn path_extracts_dynamic_segment() {
+        let req = TestRequest::default().param("id", "42").to_http_request();
+        let mut payload = Payload::None;
+
+        let Path(extracted) = Path::<Id>::from_request(&req, &mut payload).await.unwrap();
+
+        assert_eq!(extracted.id, 42);
+    }
+
+    #[actix_web::test]
+    async fn query_extracts_param() {
+        let req = TestRequest::default().uri("/?id=7").to_http_request();
+        let mut payload = Payload::None;
+
+        let Query(extracted) = Query::<Id>::from_request(&req, &mut payload).await.unwrap();
+
+        assert_eq!(extracted.id, 7);
+    }
+
+    #[actix_web::test]
+    async fn json_extracts_body() {
+        let (req, mut payload) = TestRequest::default()
+            .insert_header(("content-type", "application/json"))
+            .set_json(&Id { id: 9 })
+            .to_http_parts();
+
+        let Json(extracted) = Json::<Id>::from_request(&req, &mut payload).await.unwrap();
+
+        assert_eq!(extracted.id, 9);
+    }
+
+    #[actix_web::test]
+    async fn data_resolves_registered_app_data() {
+        let mut app_data = AppDataMap::new();
+        app_data.insert(
+            TypeId::of::<String>(),
+            Arc::new("hello".to_string()) as Arc<dyn Any + Send + Sync>,
+        );
+        let req = TestRequest::default()
+            .app_data(Arc::new(app_data))
+            .to_http_request();
+        let mut payload = Payload::None;
+
+        let data = Data::<String>::from_request(&req, &mut payload).await.unwrap();
+
+        assert_eq!(*data, "hello");
+    }
+
+    #[actix_web::test]
+    async fn data_errors_when_type_not_registered() {
+        let req = TestRequest::default()
+            .app_data(Arc::new(AppDataMap::new()))
+            .to_http_request();
+        let mut payload = Payload::None;
+
+        let result = Data::<String>::from_request(&req, &mut payload).await;
+
+        assert!(result.is_err());
+    }
+}